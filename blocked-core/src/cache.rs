@@ -0,0 +1,84 @@
+//! On-disk cache for issue/PR status lookups, keyed by the resolved API URL.
+//!
+//! Avoids refetching the same issue over and over when many `blocked!`/`#[blocked_attr]` invocations across a
+//! workspace reference it during a single `cargo build`.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    fetched_at: u64,
+    body: String,
+}
+
+/// Fetch `url`'s response body, returning a cached copy if one exists and is younger than `ttl`.
+///
+/// A request failure is returned as `Err` rather than panicking, so a transient network blip never aborts
+/// compilation -- the caller turns it into a compiler notice instead.
+pub(crate) fn get_cached(
+    client: &reqwest::blocking::Client,
+    url: &Url,
+    ttl: Duration,
+) -> Result<String, String> {
+    let cache_path = cache_path_for(url);
+
+    if let Some(entry) = read_entry(&cache_path) {
+        if now_secs().saturating_sub(entry.fetched_at) < ttl.as_secs() {
+            return Ok(entry.body);
+        }
+    }
+
+    let response = client
+        .get(url.clone())
+        .send()
+        .map_err(|err| err.to_string())?;
+    let body = response.text().map_err(|err| err.to_string())?;
+    write_entry(
+        &cache_path,
+        &CacheEntry {
+            fetched_at: now_secs(),
+            body: body.clone(),
+        },
+    );
+    Ok(body)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// The on-disk path for a given API URL's cache entry, inside the system temp directory.
+fn cache_path_for(url: &Url) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    url.as_str().hash(&mut hasher);
+    std::env::temp_dir()
+        .join("blocked-rs-cache")
+        .join(format!("{:x}.json", hasher.finish()))
+}
+
+/// Best-effort cache read: any missing file or corrupt entry is treated as a cache miss.
+fn read_entry(path: &PathBuf) -> Option<CacheEntry> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Best-effort cache write: failures (e.g. a read-only temp dir) are silently ignored, since a cache is an
+/// optimisation and must never be the reason a build fails.
+fn write_entry(path: &PathBuf, entry: &CacheEntry) {
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(contents) = serde_json::to_string(entry) {
+        let _ = fs::write(path, contents);
+    }
+}
@@ -0,0 +1,631 @@
+//! Shared, network-aware core for the `blocked` proc-macro crate and its companion `blocked` binary.
+//!
+//! This is plain library code with no `proc_macro` dependency. It exists as its own crate because
+//! `blocked`'s lib target has `proc-macro = true`, and rustc forbids a proc-macro crate from exporting
+//! anything other than `#[proc_macro]`/`#[proc_macro_attribute]`/`#[proc_macro_derive]` functions -- so
+//! the pattern parsing, status fetching and caching logic that both the macros and the binary need has
+//! to live somewhere both can depend on normally.
+
+mod cache;
+pub mod scan;
+
+use std::path::Path;
+use std::time::Duration;
+
+use git2::Repository;
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::header::{self, HeaderMap};
+use serde::Deserialize;
+use url::Url;
+
+lazy_static! {
+    static ref ISSUE: Regex = Regex::new(r"#?(\d+)").unwrap();
+    static ref REPOISSUE: Regex = Regex::new(r"([\w-]+)[#/](\d+)").unwrap();
+    static ref OWNERREPOISSUE: Regex = Regex::new(r"([\w-]+)/([\w-]+)[#/](\d+)").unwrap();
+    static ref URL: Regex = Regex::new(
+        r"https?://(?P<host>[\w.-]+)/(?P<owner>[\w-]+)/(?P<repo>[\w-]+)/issues/(?P<num>\d+)"
+    )
+    .unwrap();
+    static ref PULL_URL: Regex = Regex::new(
+        r"https?://(?P<host>[\w.-]+)/(?P<owner>[\w-]+)/(?P<repo>[\w-]+)/pull/(?P<num>\d+)"
+    )
+    .unwrap();
+    // Accepts an optional `https://`/`ssh://`/`git://` scheme, an optional `user@` (as in `git@github.com`), a host,
+    // an optional `:port` (only meaningful for a URL-like remote, e.g. a self-hosted instance on a non-default
+    // SSH port), either a `:` or `/` separator (SCP-like vs URL-like remotes), owner/repo, and an optional
+    // trailing `.git`.
+    static ref REMOTE: Regex = Regex::new(
+        r"^(?:(?:https?|git|ssh)://)?(?:[\w.-]+@)?(?P<host>[\w.-]+)(?::\d+)?[:/](?P<owner>[\w-]+)/(?P<repo>[\w.-]+?)/?$"
+    )
+    .unwrap();
+    static ref GITHUB_BASE: Url = Url::parse("https://api.github.com/repos/").unwrap();
+}
+
+/// Which git hosting service an issue reference targets.
+///
+/// `GitHub`'s `host` is `None` for github.com itself and `Some(host)` for a GitHub Enterprise instance, since both
+/// speak (almost) the same API shape, just against a different base URL -- see [`GitServer::from_host`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitServer {
+    GitHub { host: Option<String> },
+    GitLab { host: String },
+}
+
+impl GitServer {
+    /// Pick a `GitServer` based on a host name taken from a URL or git remote.
+    ///
+    /// `github.com` itself is always GitHub. Any other host is GitHub Enterprise if it's listed in
+    /// `BLOCKED_GITHUB_HOSTS` (a comma-separated list of hostnames), since there's no way to tell a GHE instance
+    /// from a GitLab instance from the host name alone. Anything else is assumed to be GitLab.
+    pub fn from_host(host: &str) -> Self {
+        if host.eq_ignore_ascii_case("github.com") {
+            GitServer::GitHub { host: None }
+        } else if is_listed_github_host(host) {
+            GitServer::GitHub {
+                host: Some(host.to_owned()),
+            }
+        } else {
+            GitServer::GitLab {
+                host: host.to_owned(),
+            }
+        }
+    }
+
+    /// The environment variable that holds the API token for this server.
+    pub fn api_key_env_var(&self) -> &'static str {
+        match self {
+            GitServer::GitHub { .. } => "BLOCKED_GITHUB_API_KEY",
+            GitServer::GitLab { .. } => "BLOCKED_GITLAB_API_KEY",
+        }
+    }
+}
+
+/// Whether `host` is listed in `BLOCKED_GITHUB_HOSTS` as a GitHub Enterprise instance.
+fn is_listed_github_host(host: &str) -> bool {
+    std::env::var("BLOCKED_GITHUB_HOSTS")
+        .map(|hosts| {
+            hosts
+                .split(',')
+                .any(|candidate| candidate.trim().eq_ignore_ascii_case(host))
+        })
+        .unwrap_or(false)
+}
+
+/// Data returned from the Github or GitLab issue API
+///
+/// Currently we only care about the state. Github uses `open`/`closed`, GitLab uses `opened`/`closed`; both are
+/// treated as the same shape here and [`fetch_status`] normalises them.
+// TODO: Add the date it was closed here?
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IssueResponse {
+    Ok { state: String },
+    Err { message: String },
+}
+
+/// Data returned from the Github or GitLab pull/merge request API
+///
+/// `merged_at` is only set once the PR has actually been merged, which lets us tell a landed fix apart from one
+/// that was simply closed.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PullRequestResponse {
+    Ok {
+        state: String,
+        merged_at: Option<String>,
+    },
+    Err {
+        message: String,
+    },
+}
+
+/// Whether an issue pattern refers to an issue or a pull/merge request.
+///
+/// Issue and PR numbers share the same namespace on Github, so callers that want to gate a workaround on a fix
+/// landing (rather than an issue closing) point at a `/pull/<n>` URL or prefix a shorthand pattern with `pr:`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefKind {
+    Issue,
+    PullRequest,
+}
+
+/// The state of a referenced issue or PR, independent of how the caller wants to report it.
+///
+/// The `blocked!`/`#[blocked_attr]` macros map this to a compiler diagnostic; the `blocked` binary's [`scan`]
+/// support maps it to a line in its own report instead. Keeping this mapping-agnostic is what lets both share
+/// [`fetch_status`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefStatus {
+    /// The issue is open, or the PR is open/unmerged.
+    Open,
+    /// The issue was closed.
+    ClosedIssue,
+    /// The PR was merged.
+    MergedPr,
+    /// The PR was closed without being merged.
+    ClosedPr,
+    /// The status couldn't be determined: a request failure, or a response this crate doesn't understand.
+    Unknown(String),
+}
+
+/// Fetch an issue/PR's status (via the on-disk cache) and interpret the response into a [`RefStatus`].
+///
+/// Shared by the `blocked!`/`#[blocked_attr]` macros and the `blocked` binary's [`scan`] support, so both report
+/// exactly the same states for exactly the same reference.
+pub fn fetch_status(
+    client: &reqwest::blocking::Client,
+    kind: RefKind,
+    url: &Url,
+    ttl: Duration,
+) -> Result<RefStatus, String> {
+    let body = cache::get_cached(client, url, ttl)?;
+
+    Ok(match kind {
+        RefKind::Issue => match serde_json::from_str::<IssueResponse>(&body) {
+            Ok(IssueResponse::Err { message }) => {
+                RefStatus::Unknown(format!("Error fetching issue: {}", message))
+            }
+            Ok(IssueResponse::Ok { state }) => match state.as_str() {
+                "open" | "opened" => RefStatus::Open,
+                "closed" => RefStatus::ClosedIssue,
+                other => RefStatus::Unknown(format!("Unrecognised issue state: {}", other)),
+            },
+            Err(err) => RefStatus::Unknown(format!("Could not parse issue response: {}", err)),
+        },
+        RefKind::PullRequest => match serde_json::from_str::<PullRequestResponse>(&body) {
+            Ok(PullRequestResponse::Err { message }) => {
+                RefStatus::Unknown(format!("Error fetching pull request: {}", message))
+            }
+            Ok(PullRequestResponse::Ok { state, merged_at }) => match state.as_str() {
+                "open" | "opened" => RefStatus::Open,
+                // GitHub reports a merged PR as `closed` with `merged_at` set; GitLab gives merged merge requests
+                // their own `merged` state instead, with no `merged_at` in the payload.
+                "closed" if merged_at.is_some() => RefStatus::MergedPr,
+                "merged" => RefStatus::MergedPr,
+                "closed" => RefStatus::ClosedPr,
+                other => RefStatus::Unknown(format!("Unrecognised PR state: {}", other)),
+            },
+            Err(err) => {
+                RefStatus::Unknown(format!("Could not parse pull request response: {}", err))
+            }
+        },
+    })
+}
+
+/// Get a client suitable for interacting with the Github or GitLab API
+pub fn api_client(server: &GitServer, api_key: Option<&str>) -> reqwest::blocking::Client {
+    let mut headers = HeaderMap::new();
+    if let Some(api_key) = api_key {
+        match server {
+            GitServer::GitHub { .. } => {
+                headers.insert(
+                    header::AUTHORIZATION,
+                    header::HeaderValue::from_str(api_key).unwrap(),
+                );
+            }
+            GitServer::GitLab { .. } => {
+                headers.insert(
+                    header::HeaderName::from_static("private-token"),
+                    header::HeaderValue::from_str(api_key).unwrap(),
+                );
+            }
+        }
+    }
+    headers.insert(
+        header::USER_AGENT,
+        header::HeaderValue::from_static("blocked-rs"),
+    );
+    reqwest::blocking::Client::builder()
+        .default_headers(headers)
+        .timeout(request_timeout())
+        .build()
+        .unwrap()
+}
+
+/// Default request timeout, in seconds, when `BLOCKED_REQUEST_TIMEOUT_SECS` isn't set.
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// The timeout to use for the issue/PR status request, so a hung API call can't stall the edit-compile cycle.
+fn request_timeout() -> Duration {
+    Duration::from_secs(
+        std::env::var("BLOCKED_REQUEST_TIMEOUT_SECS")
+            .ok()
+            .and_then(|val| val.parse().ok())
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS),
+    )
+}
+
+/// Build the issue/PR API URL for a given server, owner, repo and number.
+fn ref_url(
+    server: &GitServer,
+    kind: RefKind,
+    owner: &str,
+    repo: &str,
+    num: &str,
+) -> Result<Url, syn::Error> {
+    let segment = match (server, kind) {
+        (GitServer::GitHub { .. }, RefKind::Issue) => "issues",
+        (GitServer::GitHub { .. }, RefKind::PullRequest) => "pulls",
+        (GitServer::GitLab { .. }, RefKind::Issue) => "issues",
+        (GitServer::GitLab { .. }, RefKind::PullRequest) => "merge_requests",
+    };
+    match server {
+        GitServer::GitHub { host: None } => GITHUB_BASE
+            .clone()
+            .join(&format!("{}/{}/{}/{}", owner, repo, segment, num))
+            .map_err(|_| error("Could not join URL fragments")),
+        GitServer::GitHub { host: Some(host) } => {
+            // GitHub Enterprise instances expose the same REST shape as github.com, just rooted under
+            // /api/v3 on the instance's own host instead of api.github.com.
+            Url::parse(&format!(
+                "https://{}/api/v3/repos/{}/{}/{}/{}",
+                host, owner, repo, segment, num
+            ))
+            .map_err(|_| error("Could not build GitHub Enterprise API URL"))
+        }
+        GitServer::GitLab { host } => {
+            // GitLab addresses projects by url-encoded `owner/repo` path.
+            Url::parse(&format!(
+                "https://{}/api/v4/projects/{}%2F{}/{}/{}",
+                host, owner, repo, segment, num
+            ))
+            .map_err(|_| error("Could not build GitLab API URL"))
+        }
+    }
+}
+
+/// Parse an issue pattern, resolving shorthand forms against the git repository containing the current directory.
+/// Possible forms are documented on the `blocked!` macro's crate documentation.
+pub fn parse_issue_pattern(pattern: &str) -> Result<(GitServer, RefKind, Url), syn::Error> {
+    parse_issue_pattern_in(pattern, Path::new("."))
+}
+
+/// Parse an issue pattern as [`parse_issue_pattern`] does, but resolve shorthand forms (which need the
+/// `upstream`/`origin` remote) against the git repository containing `base` rather than the current directory.
+///
+/// Used by the `blocked` binary's [`scan`] support, which scans an arbitrary tree rather than the crate currently
+/// being compiled.
+pub fn parse_issue_pattern_in(
+    pattern: &str,
+    base: &Path,
+) -> Result<(GitServer, RefKind, Url), syn::Error> {
+    if let Some(captures) = PULL_URL.captures(pattern) {
+        let host = captures.name("host").unwrap().as_str();
+        let server = GitServer::from_host(host);
+        let url = ref_url(
+            &server,
+            RefKind::PullRequest,
+            captures.name("owner").unwrap().as_str(),
+            captures.name("repo").unwrap().as_str(),
+            captures.name("num").unwrap().as_str(),
+        )?;
+        return Ok((server, RefKind::PullRequest, url));
+    }
+    if let Some(captures) = URL.captures(pattern) {
+        let host = captures.name("host").unwrap().as_str();
+        let server = GitServer::from_host(host);
+        let url = ref_url(
+            &server,
+            RefKind::Issue,
+            captures.name("owner").unwrap().as_str(),
+            captures.name("repo").unwrap().as_str(),
+            captures.name("num").unwrap().as_str(),
+        )?;
+        return Ok((server, RefKind::Issue, url));
+    }
+
+    // A leading `pr:` marks a shorthand pattern as a pull-request reference rather than an issue.
+    let (kind, pattern) = match pattern.strip_prefix("pr:") {
+        Some(rest) => (RefKind::PullRequest, rest),
+        None => (RefKind::Issue, pattern),
+    };
+
+    if let Some(captures) = OWNERREPOISSUE.captures(pattern) {
+        let (server, _, _) = try_get_org_repo(base)?;
+        let url = ref_url(
+            &server,
+            kind,
+            captures.get(1).unwrap().as_str(),
+            captures.get(2).unwrap().as_str(),
+            captures.get(3).unwrap().as_str(),
+        )?;
+        return Ok((server, kind, url));
+    }
+    if let Some(captures) = REPOISSUE.captures(pattern) {
+        let (server, org, _) = try_get_org_repo(base)?;
+        let url = ref_url(
+            &server,
+            kind,
+            &org,
+            captures.get(1).unwrap().as_str(),
+            captures.get(2).unwrap().as_str(),
+        )?;
+        return Ok((server, kind, url));
+    }
+    if let Some(captures) = ISSUE.captures(pattern) {
+        let (server, org, repo) = try_get_org_repo(base)?;
+        let url = ref_url(
+            &server,
+            kind,
+            &org,
+            &repo,
+            captures.get(1).unwrap().as_str(),
+        )?;
+        return Ok((server, kind, url));
+    }
+    Err(error("Could not parse issue pattern"))
+}
+
+/// Try to get the git host, organisation and repository from the git repo containing `base`.
+///
+/// This is used for shorthand issue patterns.
+fn try_get_org_repo(base: &Path) -> Result<(GitServer, String, String), syn::Error> {
+    let repo =
+        Repository::discover(base).map_err(|_| error("Could not find or open a git repository"))?;
+
+    let remote = if let Ok(remote) = repo.find_remote("upstream") {
+        Some(remote)
+    } else {
+        repo.find_remote("origin").ok()
+    }
+    .ok_or_else(|| error("Could not find an 'upstream' or 'origin' remote"))?;
+
+    let captures = REMOTE
+        .captures(
+            remote
+                .url()
+                .ok_or_else(|| error("Remote URL not valid unicode"))?,
+        )
+        .ok_or_else(|| error("Failed to parse remote URL"))?;
+
+    let host = captures.name("host").unwrap().as_str();
+    let owner = captures.name("owner").unwrap().as_str().to_owned();
+    let repo_name = captures
+        .name("repo")
+        .unwrap()
+        .as_str()
+        .trim_end_matches(".git")
+        .to_owned();
+
+    Ok((GitServer::from_host(host), owner, repo_name))
+}
+
+fn error(message: impl AsRef<str>) -> syn::Error {
+    syn::Error::new(proc_macro2::Span::call_site(), message.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    // `BLOCKED_GITHUB_HOSTS` is process-global, so tests that set it are serialised on this lock rather than
+    // risking a race against each other when the test binary runs them concurrently.
+    static GITHUB_HOSTS_ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn from_host_recognises_github_dot_com() {
+        assert_eq!(
+            GitServer::from_host("github.com"),
+            GitServer::GitHub { host: None }
+        );
+        assert_eq!(
+            GitServer::from_host("GitHub.COM"),
+            GitServer::GitHub { host: None }
+        );
+    }
+
+    #[test]
+    fn from_host_defaults_unlisted_hosts_to_gitlab() {
+        let _guard = GITHUB_HOSTS_ENV_LOCK.lock().unwrap();
+        std::env::remove_var("BLOCKED_GITHUB_HOSTS");
+        assert_eq!(
+            GitServer::from_host("git.example.com"),
+            GitServer::GitLab {
+                host: "git.example.com".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn from_host_recognises_listed_github_enterprise_hosts() {
+        let _guard = GITHUB_HOSTS_ENV_LOCK.lock().unwrap();
+        std::env::set_var("BLOCKED_GITHUB_HOSTS", "ghe.example.com, other.example.com");
+        assert_eq!(
+            GitServer::from_host("ghe.example.com"),
+            GitServer::GitHub {
+                host: Some("ghe.example.com".to_string())
+            }
+        );
+        assert_eq!(
+            GitServer::from_host("unlisted.example.com"),
+            GitServer::GitLab {
+                host: "unlisted.example.com".to_string()
+            }
+        );
+        std::env::remove_var("BLOCKED_GITHUB_HOSTS");
+    }
+
+    #[test]
+    fn remote_regex_parses_https_url_with_dot_git_suffix() {
+        let captures = REMOTE
+            .captures("https://github.com/serde-rs/serde.git")
+            .unwrap();
+        assert_eq!(&captures["host"], "github.com");
+        assert_eq!(&captures["owner"], "serde-rs");
+        assert_eq!(&captures["repo"], "serde.git");
+    }
+
+    #[test]
+    fn remote_regex_parses_scp_like_remote() {
+        let captures = REMOTE
+            .captures("git@github.com:serde-rs/serde.git")
+            .unwrap();
+        assert_eq!(&captures["host"], "github.com");
+        assert_eq!(&captures["owner"], "serde-rs");
+        assert_eq!(&captures["repo"], "serde.git");
+    }
+
+    #[test]
+    fn remote_regex_parses_ssh_url_without_dot_git_suffix() {
+        let captures = REMOTE
+            .captures("ssh://git@gitlab.example.com/owner/repo")
+            .unwrap();
+        assert_eq!(&captures["host"], "gitlab.example.com");
+        assert_eq!(&captures["owner"], "owner");
+        assert_eq!(&captures["repo"], "repo");
+    }
+
+    #[test]
+    fn remote_regex_parses_url_with_explicit_port() {
+        let captures = REMOTE
+            .captures("ssh://git@ghe.example.com:2222/owner/repo.git")
+            .unwrap();
+        assert_eq!(&captures["host"], "ghe.example.com");
+        assert_eq!(&captures["owner"], "owner");
+        assert_eq!(&captures["repo"], "repo.git");
+    }
+
+    #[test]
+    fn parse_issue_pattern_in_resolves_explicit_issue_url() {
+        let (server, kind, url) = parse_issue_pattern_in(
+            "https://github.com/serde-rs/serde/issues/423",
+            Path::new("."),
+        )
+        .unwrap();
+        assert_eq!(server, GitServer::GitHub { host: None });
+        assert_eq!(kind, RefKind::Issue);
+        assert_eq!(
+            url.as_str(),
+            "https://api.github.com/repos/serde-rs/serde/issues/423"
+        );
+    }
+
+    #[test]
+    fn parse_issue_pattern_in_resolves_explicit_pull_url_on_gitlab() {
+        let (server, kind, url) = parse_issue_pattern_in(
+            "https://gitlab.example.com/owner/repo/pull/7",
+            Path::new("."),
+        )
+        .unwrap();
+        assert_eq!(
+            server,
+            GitServer::GitLab {
+                host: "gitlab.example.com".to_string()
+            }
+        );
+        assert_eq!(kind, RefKind::PullRequest);
+        assert_eq!(
+            url.as_str(),
+            "https://gitlab.example.com/api/v4/projects/owner%2Frepo/merge_requests/7"
+        );
+    }
+
+    /// Create a throwaway git repository with a single remote, for exercising the shorthand-pattern resolution
+    /// path in [`try_get_org_repo`]/[`parse_issue_pattern_in`] without touching a real repo.
+    fn init_repo_with_remote(remote_name: &str, remote_url: &str) -> std::path::PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "blocked-core-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let repo = Repository::init(&dir).unwrap();
+        repo.remote(remote_name, remote_url).unwrap();
+        dir
+    }
+
+    #[test]
+    fn try_get_org_repo_prefers_upstream_over_origin() {
+        let dir = init_repo_with_remote("origin", "git@github.com:someone/fork.git");
+        Repository::open(&dir)
+            .unwrap()
+            .remote("upstream", "git@github.com:serde-rs/serde.git")
+            .unwrap();
+
+        let (server, owner, repo) = try_get_org_repo(&dir).unwrap();
+        assert_eq!(server, GitServer::GitHub { host: None });
+        assert_eq!(owner, "serde-rs");
+        assert_eq!(repo, "serde");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn try_get_org_repo_falls_back_to_origin() {
+        let dir = init_repo_with_remote("origin", "https://gitlab.example.com/owner/repo.git");
+
+        let (server, owner, repo) = try_get_org_repo(&dir).unwrap();
+        assert_eq!(
+            server,
+            GitServer::GitLab {
+                host: "gitlab.example.com".to_string()
+            }
+        );
+        assert_eq!(owner, "owner");
+        assert_eq!(repo, "repo");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn parse_issue_pattern_in_resolves_shorthand_against_repo_remote() {
+        let dir = init_repo_with_remote("origin", "git@github.com:serde-rs/serde.git");
+
+        let (server, kind, url) = parse_issue_pattern_in("serde-rs/serde#423", &dir).unwrap();
+        assert_eq!(server, GitServer::GitHub { host: None });
+        assert_eq!(kind, RefKind::Issue);
+        assert_eq!(
+            url.as_str(),
+            "https://api.github.com/repos/serde-rs/serde/issues/423"
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn parse_issue_pattern_in_resolves_bare_repo_shorthand_forms() {
+        let dir = init_repo_with_remote("origin", "git@github.com:serde-rs/serde.git");
+
+        for pattern in ["serde#423", "serde/423"] {
+            let (server, kind, url) = parse_issue_pattern_in(pattern, &dir).unwrap();
+            assert_eq!(server, GitServer::GitHub { host: None });
+            assert_eq!(kind, RefKind::Issue);
+            assert_eq!(
+                url.as_str(),
+                "https://api.github.com/repos/serde-rs/serde/issues/423"
+            );
+        }
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn parse_issue_pattern_in_resolves_bare_number_shorthand() {
+        let dir = init_repo_with_remote("origin", "git@github.com:serde-rs/serde.git");
+
+        let (server, kind, url) = parse_issue_pattern_in("423", &dir).unwrap();
+        assert_eq!(server, GitServer::GitHub { host: None });
+        assert_eq!(kind, RefKind::Issue);
+        assert_eq!(
+            url.as_str(),
+            "https://api.github.com/repos/serde-rs/serde/issues/423"
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn try_get_org_repo_errors_without_a_git_repository() {
+        let dir =
+            std::env::temp_dir().join(format!("blocked-core-test-no-repo-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert!(try_get_org_repo(&dir).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
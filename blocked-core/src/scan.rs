@@ -0,0 +1,74 @@
+//! Support for the `blocked` binary: find every issue/PR reference in a chunk of source text and check its status
+//! without going through the `blocked!`/`#[blocked_attr]` proc macros.
+//!
+//! This is deliberately thin -- it reuses [`parse_issue_pattern`](crate::parse_issue_pattern) and
+//! [`fetch_status`](crate::fetch_status), the same pattern parser and status-fetching logic the macros use, so a
+//! reference is guaranteed to resolve and report identically whether it's found by `cargo build` or by the binary.
+
+use std::path::Path;
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{api_client, fetch_status, parse_issue_pattern_in, RefStatus};
+
+lazy_static! {
+    // `blocked!("...")` and `#[blocked_attr("...")]` both pass the issue pattern as their first string literal
+    // argument, so one pattern covers both call sites.
+    static ref INVOCATION: Regex =
+        Regex::new(r#"(?:blocked!|#\s*\[\s*blocked_attr)\s*\(\s*"(?P<pattern>[^"]+)""#).unwrap();
+    // A plain `TODO(owner/repo#n)` comment, for workarounds that predate (or don't want) a `blocked!` call.
+    static ref TODO_COMMENT: Regex = Regex::new(r"TODO\((?P<pattern>[\w.-]+/[\w.-]+#\d+)\)").unwrap();
+}
+
+/// One issue/PR reference found in a source file, with its 1-based line and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub pattern: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Scan a source file's contents for `blocked!`/`#[blocked_attr]` invocations and `TODO(owner/repo#n)` comments,
+/// returning every issue/PR pattern found.
+///
+/// This only looks line-by-line, so a pattern split across lines (an invocation whose string literal is wrapped,
+/// say) won't be found -- in practice that's rare enough not to be worth the complexity of a whole-file scan.
+pub fn find_references(contents: &str) -> Vec<Reference> {
+    let mut found = Vec::new();
+    for (line_no, line) in contents.lines().enumerate() {
+        for captures in INVOCATION
+            .captures_iter(line)
+            .chain(TODO_COMMENT.captures_iter(line))
+        {
+            let pattern = captures.name("pattern").unwrap();
+            found.push(Reference {
+                pattern: pattern.as_str().to_owned(),
+                line: line_no + 1,
+                column: pattern.start() + 1,
+            });
+        }
+    }
+    found
+}
+
+/// Resolve and check one [`Reference`], reusing the same pattern parser and state-fetching logic as the
+/// `blocked!`/`#[blocked_attr]` macros.
+///
+/// `base` is the root of the tree being scanned, so that a shorthand pattern (one with no explicit owner/repo/host)
+/// resolves against the scanned tree's `upstream`/`origin` remote rather than the current directory's.
+///
+/// Unlike the macros, this always makes the request: the binary is invoked explicitly to check references, so
+/// there's no edit-compile cycle to protect and no need to gate on an API key or a detected CI environment.
+pub fn check_reference(
+    reference: &Reference,
+    base: &Path,
+    ttl: Duration,
+) -> Result<RefStatus, String> {
+    let (server, kind, url) =
+        parse_issue_pattern_in(&reference.pattern, base).map_err(|err| err.to_string())?;
+    let api_key = std::env::var(server.api_key_env_var()).ok();
+    let client = api_client(&server, api_key.as_deref());
+    fetch_status(&client, kind, &url, ttl)
+}
@@ -0,0 +1,143 @@
+//! `blocked` -- walk a source tree, find every reference the `blocked!`/`#[blocked_attr]` macros (or a plain
+//! `TODO(owner/repo#n)` comment) would check, and report any that are already closed or merged, or whose status
+//! can't be determined.
+//!
+//! This moves the network work out of the compiler and into one explicit pass that can run as its own pre-merge
+//! check or CI job, independent of -- and faster than -- the per-build checks the proc macro does, since every
+//! reference in the tree is resolved and checked concurrently in one go. Exits non-zero if it finds a closed
+//! reference or one whose status can't be determined, so it can gate CI on its own.
+//!
+//! ```text
+//! blocked [path]
+//! ```
+//!
+//! `path` defaults to the current directory. `BLOCKED_GITHUB_API_KEY`/`BLOCKED_GITLAB_API_KEY` and
+//! `BLOCKED_CACHE_TTL_SECS` are honoured exactly as they are by the proc macro.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use blocked_core::scan::{check_reference, find_references, Reference};
+use blocked_core::RefStatus;
+
+/// How many issue/PR status requests to have in flight at once.
+const MAX_CONCURRENT_REQUESTS: usize = 16;
+
+/// A reference whose status turned out to be worth reporting: closed, merged, or unknown.
+struct Finding {
+    file: PathBuf,
+    reference: Reference,
+    status: RefStatus,
+}
+
+fn main() -> ExitCode {
+    let root = std::env::args().nth(1).unwrap_or_else(|| ".".to_string());
+    let ttl = Duration::from_secs(
+        std::env::var("BLOCKED_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|val| val.parse().ok())
+            .unwrap_or(300),
+    );
+
+    let files = match collect_rust_files(Path::new(&root)) {
+        Ok(files) => files,
+        Err(err) => {
+            eprintln!("blocked: could not walk {}: {}", root, err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let references: Vec<(PathBuf, Reference)> = files
+        .into_iter()
+        .flat_map(|path| match std::fs::read_to_string(&path) {
+            Ok(contents) => find_references(&contents)
+                .into_iter()
+                .map(|reference| (path.clone(), reference))
+                .collect(),
+            Err(err) => {
+                eprintln!("blocked: could not read {}: {}", path.display(), err);
+                Vec::new()
+            }
+        })
+        .collect();
+
+    // Every reference resolves to its own independent API request, so check them in parallel rather than one at a
+    // time -- a tree with many workarounds pointing at the same slow host would otherwise take forever. Checked in
+    // batches of `MAX_CONCURRENT_REQUESTS` rather than all at once, so a tree with thousands of references doesn't
+    // spawn thousands of threads and hammer the API into rate-limiting itself.
+    let root_path = Path::new(&root);
+    let mut findings = Vec::new();
+    for batch in references.chunks(MAX_CONCURRENT_REQUESTS) {
+        let mut batch_findings: Vec<Finding> = std::thread::scope(|scope| {
+            batch
+                .iter()
+                .map(|(file, reference)| {
+                    scope.spawn(move || match check_reference(reference, root_path, ttl) {
+                        Ok(RefStatus::Open) => None,
+                        Ok(status) => Some(Finding {
+                            file: file.clone(),
+                            reference: reference.clone(),
+                            status,
+                        }),
+                        Err(err) => Some(Finding {
+                            file: file.clone(),
+                            reference: reference.clone(),
+                            status: RefStatus::Unknown(err),
+                        }),
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .filter_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        findings.append(&mut batch_findings);
+    }
+
+    if findings.is_empty() {
+        return ExitCode::SUCCESS;
+    }
+
+    for finding in &findings {
+        println!(
+            "{}:{}:{}: {:?} -- {}",
+            finding.file.display(),
+            finding.reference.line,
+            finding.reference.column,
+            finding.reference.pattern,
+            describe(&finding.status),
+        );
+    }
+    ExitCode::FAILURE
+}
+
+/// Human-readable description of a non-open [`RefStatus`], for the binary's report.
+fn describe(status: &RefStatus) -> String {
+    match status {
+        RefStatus::Open => unreachable!("open references are filtered out before this is called"),
+        RefStatus::ClosedIssue => "issue was closed".to_string(),
+        RefStatus::MergedPr => "PR merged -- time to remove the workaround".to_string(),
+        RefStatus::ClosedPr => "PR closed without merging".to_string(),
+        RefStatus::Unknown(message) => message.clone(),
+    }
+}
+
+/// Recursively collect every `.rs` file under `root`, skipping `target` directories.
+fn collect_rust_files(root: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                if path.file_name().and_then(|name| name.to_str()) != Some("target") {
+                    dirs.push(path);
+                }
+            } else if path.extension().and_then(|ext| ext.to_str()) == Some("rs") {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
@@ -5,8 +5,22 @@
 //!
 //! Because this requires network access, it is recommended this is only run in CI builds so as to not slow down the edit-run-debug cycle.
 //!
+//! Setting the `BLOCKED_HARD_FAIL` environment variable (to anything other than `0`/`false`) escalates a closed-issue
+//! notice from a warning to a hard compile error, which is useful for CI jobs that should fail until someone removes
+//! the workaround.
+//!
+//! Issue/PR status is cached on disk (keyed by the resolved API URL) so that many invocations referencing the same
+//! issue during one `cargo build` only fetch it once; the cache TTL defaults to 300 seconds and can be overridden
+//! with `BLOCKED_CACHE_TTL_SECS`. Setting `BLOCKED_IGNORE` (to anything other than `0`/`false`) skips the check
+//! entirely, which is useful for an offline build or to stay under API rate limits.
+//!
+//! The request to the issue/PR API times out after 10 seconds by default (override with
+//! `BLOCKED_REQUEST_TIMEOUT_SECS`) so a hung API call can't stall the edit-compile cycle. A request that still fails,
+//! or a response this crate doesn't understand, is reported as a compiler notice rather than aborting the build --
+//! escalated to an error under `BLOCKED_HARD_FAIL` like a closed-issue notice, since a status that can't be verified
+//! shouldn't silently pass either.
+//!
 //! ```
-//! // An attribute-like procedural macro is on the todo-list
 //! #![feature(proc_macro_hygiene)]
 //!
 //! use blocked::blocked;
@@ -20,49 +34,70 @@
 //! # }
 //! ```
 //!
+//! An attribute form is also available for annotating a whole function, impl block or module rather than a single
+//! statement. Rust's macro namespace doesn't allow a bang macro and an attribute macro to share a name, so the
+//! attribute is exported as `blocked_attr` rather than `blocked`:
+//!
+//! ```
+//! use blocked::blocked_attr;
+//!
+//! #[blocked_attr("1", reason = "This code can be removed when the issue is closed")]
+//! fn hacky_workaround() {}
+//! ```
+//!
 //! # Issue patterns
 //!
-//! The following issue specifiers are supported (Github only for now)
+//! The following issue specifiers are supported
 //! * `#423` or `423`. Repository and organisation are pulled from the upstream or origin remote if they exist.
 //! * `serde#423` or `serde/423` Organisation is pulled from upstream or origin remote if they exist.
 //! * `serde-rs/serde#423` or `serde-rs/serde/423`
 //! * `http(s)://github.com/serde-rs/serde/issues/423`
+//!
+//! Any shorthand pattern above can be prefixed with `pr:` (e.g. `pr:423`) to check a pull/merge request's
+//! merged/closed state instead of an issue's open/closed state, and `http(s)://github.com/serde-rs/serde/pull/423`
+//! is recognised directly.
+//!
+//! # Git hosts
+//!
+//! GitHub, GitLab and self-hosted instances of either are supported. For an explicit URL pattern, or for a
+//! shorthand pattern resolved against the `upstream`/`origin` git remote, the host is checked against
+//! `github.com` first, then against the comma-separated list of GitHub Enterprise hosts in
+//! `BLOCKED_GITHUB_HOSTS` (there's no way to tell a GHE instance from a GitLab instance from the host name
+//! alone), and otherwise assumed to be GitLab. GitLab requests are authenticated with `BLOCKED_GITLAB_API_KEY`
+//! rather than `BLOCKED_GITHUB_API_KEY`.
+//!
+//! # The `blocked` binary
+//!
+//! Checking a reference still costs a network round trip, and a proc macro can only ever check the one reference
+//! it was invoked on. The `blocked-core` crate this one depends on also powers a standalone `blocked` binary
+//! (`cargo run --bin blocked -- [path]`) that walks a source tree, finds every `blocked!`/`#[blocked_attr]`
+//! invocation and plain `TODO(owner/repo#n)` comment, resolves and checks all of them concurrently, and prints the
+//! file/line of any that are closed (or merged/closed, for a `pr:`-prefixed reference) or whose status can't be
+//! determined. It exits non-zero if it finds one, so it can gate a pre-merge check or a CI job on its own schedule, independent of the proc-macro's
+//! per-build checks. See the `blocked_core::scan` module for the pieces it's built from.
 
 #![feature(proc_macro_diagnostic)]
 
 extern crate proc_macro;
 
+use std::time::Duration;
+
 use proc_macro::{Diagnostic, Level, Span, TokenStream};
-use syn::{parse::Parser, punctuated::Punctuated, LitStr, Token};
-
-use git2::Repository;
-use lazy_static::lazy_static;
-use regex::Regex;
-use reqwest::header::{self, HeaderMap};
-use serde::Deserialize;
-use url::Url;
-
-lazy_static! {
-    static ref ISSUE: Regex = Regex::new(r"#?(\d+)").unwrap();
-    static ref REPOISSUE: Regex = Regex::new(r"[\w-]+[#/]\d+").unwrap();
-    static ref OWNERREPOISSUE: Regex = Regex::new(r"([\w-]+)/([\w-]+)[#/](\d+)").unwrap();
-    static ref URL: Regex = Regex::new(r"https?://github.com/[\w-]+/issues/[\w-]+[#/]\d+").unwrap();
-    static ref REMOTE: Regex = Regex::new(
-        r"(?:https://github.com/([\w-]+)/([\w-]+).git)|(?:git@github.com:([\w-]+)/([\w-]+).git)"
-    )
-    .unwrap();
-    static ref BASE: Url = Url::parse("https://api.github.com/repos/").unwrap();
-}
+use syn::{
+    parse::{Parse, ParseStream, Parser},
+    punctuated::Punctuated,
+    LitStr, Token,
+};
 
-/// Data returned from the Github issue API
-///
-/// Currently we only care about the state (open/closed)
-// TODO: Add the date it was closed here?
-#[derive(Deserialize)]
-#[serde(untagged)]
-enum GithubIssueResponse {
-    Ok { state: String },
-    Err { message: String },
+use blocked_core::RefStatus;
+
+/// The outcome of checking an issue pattern, shared by the function-like and attribute macro entry points.
+enum BlockedOutcome {
+    /// Nothing to report: the referenced issue/PR is still open, or we declined to check it.
+    Silent,
+    /// The referenced issue/PR is closed (or merged), or its status couldn't be determined; emit a diagnostic at
+    /// this level with this message.
+    Notice { level: Level, message: String },
 }
 
 /// See the [crate documentation](index.html)
@@ -74,47 +109,156 @@ pub fn blocked(input: TokenStream) -> TokenStream {
         Err(err) => return TokenStream::from(err.to_compile_error()),
     };
 
-    // Try to resolve the issue pattern to an issue API URL
-    let url = match parse_issue_pattern(&issue_pattern) {
-        Ok(url) => url,
+    match check_blocked(&issue_pattern, reason) {
+        Ok(BlockedOutcome::Silent) => (),
+        Ok(BlockedOutcome::Notice { level, message }) => {
+            Diagnostic::spanned([Span::call_site()].as_ref(), level, message).emit()
+        }
         Err(err) => return TokenStream::from(err.to_compile_error()),
+    }
+
+    TokenStream::new()
+}
+
+/// See the [crate documentation](index.html)
+///
+/// Attribute form of [`blocked!`](macro@blocked), for annotating a whole function, impl block or module. Exported
+/// as `blocked_attr` rather than `blocked` because a bang macro and an attribute macro can't share a name.
+#[proc_macro_attribute]
+pub fn blocked_attr(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let BlockedAttrArgs {
+        issue_pattern,
+        reason,
+    } = match syn::parse(attr) {
+        Ok(args) => args,
+        Err(err) => {
+            return TokenStream::from_iter(vec![TokenStream::from(err.to_compile_error()), item])
+        }
     };
 
+    let item_span = item
+        .clone()
+        .into_iter()
+        .next()
+        .map(|tt| tt.span())
+        .unwrap_or_else(Span::call_site);
+
+    match check_blocked(&issue_pattern, reason) {
+        Ok(BlockedOutcome::Silent) => (),
+        Ok(BlockedOutcome::Notice { level, message }) => {
+            Diagnostic::spanned([item_span].as_ref(), level, message).emit()
+        }
+        Err(err) => {
+            return TokenStream::from_iter(vec![TokenStream::from(err.to_compile_error()), item])
+        }
+    }
+
+    item
+}
+
+/// Resolve an issue pattern and, if the referenced issue/PR is closed, work out what to report.
+///
+/// This is the shared core of both the [`blocked!`] function-like macro and the `#[blocked_attr]` attribute macro:
+/// both parse their own arguments into an issue pattern and reason, then delegate here for URL resolution, the
+/// status fetch and the diagnostic decision. The URL resolution, status fetch and on-disk cache all live in
+/// `blocked_core`, since a `proc-macro = true` crate like this one can't export that logic for the `blocked` binary
+/// to share otherwise.
+fn check_blocked(
+    issue_pattern: &str,
+    reason: Option<String>,
+) -> Result<BlockedOutcome, syn::Error> {
+    // Resolve the execution mode before doing any other work; `BLOCKED_IGNORE` short-circuits everything below.
+    let ttl = match resolve_mode() {
+        Mode::Noop => return Ok(BlockedOutcome::Silent),
+        Mode::Cached { ttl } => ttl,
+    };
+
+    // Try to resolve the issue pattern to an issue/PR API URL
+    let (server, kind, url) = blocked_core::parse_issue_pattern(issue_pattern)?;
+
     // Check if we have an API key or are running in a CI environment, otherwise exit silently
-    let api_key = if let Ok(key) = std::env::var("BLOCKED_GITHUB_API_KEY") {
+    let api_key = if let Ok(key) = std::env::var(server.api_key_env_var()) {
         Some(key)
     } else if let Some(_ci) = ci_detective::CI::from_env() {
         None
     } else {
-        return TokenStream::new();
+        return Ok(BlockedOutcome::Silent);
     };
 
-    let client = github_client(api_key.as_deref());
+    let client = blocked_core::api_client(&server, api_key.as_deref());
 
-    // Get issue status
-    let r = client.get(url).send().unwrap();
-    let issue = r.json::<GithubIssueResponse>().unwrap();
-    let issue_state = match issue {
-        GithubIssueResponse::Err { message } => {
-            warning(format!("Error fetching issue: {}", message));
-            return TokenStream::new();
-        }
-        GithubIssueResponse::Ok { state } => state,
-    };
+    // Fetch and interpret the status; a transient failure here (network down, API misbehaving) should never abort
+    // compilation, so it becomes a notice rather than a panic.
+    match blocked_core::fetch_status(&client, kind, &url, ttl) {
+        Ok(RefStatus::Open) => Ok(BlockedOutcome::Silent),
+        Ok(RefStatus::ClosedIssue) => Ok(closed_notice(reason, "Issue was closed.")),
+        Ok(RefStatus::MergedPr) => Ok(closed_notice(
+            reason,
+            "PR merged -- time to remove the workaround.",
+        )),
+        Ok(RefStatus::ClosedPr) => Ok(closed_notice(reason, "PR closed without merging.")),
+        Ok(RefStatus::Unknown(message)) => Ok(unexpected_notice(message)),
+        Err(err) => Ok(unexpected_notice(format!(
+            "Error fetching issue/PR status: {}",
+            err
+        ))),
+    }
+}
 
-    // Warn if the issue has been closed
-    match issue_state.as_str() {
-        "open" => (),
-        "closed" => Diagnostic::spanned(
-            [Span::call_site()].as_ref(),
-            Level::Warning,
-            reason.unwrap_or_else(|| "Issue was closed.".to_string()),
-        )
-        .emit(),
-        _ => panic!("unknown response"),
+/// The `Diagnostic` level to use for a closed-issue or closed-PR notice.
+///
+/// Defaults to `Level::Warning`. If `BLOCKED_HARD_FAIL` is set to anything other than `0` or `false`,
+/// escalates to `Level::Error` so CI builds fail until the workaround is removed.
+fn hard_fail_level() -> Level {
+    match std::env::var("BLOCKED_HARD_FAIL") {
+        Ok(val) if val != "0" && val.to_lowercase() != "false" => Level::Error,
+        _ => Level::Warning,
     }
+}
 
-    TokenStream::new()
+/// Default TTL, in seconds, for the on-disk issue/PR status cache when `BLOCKED_CACHE_TTL_SECS` isn't set.
+const DEFAULT_CACHE_TTL_SECS: u64 = 300;
+
+/// Execution mode, resolved from the environment before any network call is made.
+enum Mode {
+    /// `BLOCKED_IGNORE` is set: skip the check entirely and expand to nothing.
+    Noop,
+    /// Normal operation: fetch issue/PR status, consulting an on-disk cache first so repeated invocations across a
+    /// workspace during one `cargo build` don't each hit the API.
+    Cached { ttl: Duration },
+}
+
+/// Resolve the [`Mode`] to run in from `BLOCKED_IGNORE` and `BLOCKED_CACHE_TTL_SECS`.
+fn resolve_mode() -> Mode {
+    match std::env::var("BLOCKED_IGNORE") {
+        Ok(val) if val != "0" && val.to_lowercase() != "false" => Mode::Noop,
+        _ => Mode::Cached {
+            ttl: Duration::from_secs(
+                std::env::var("BLOCKED_CACHE_TTL_SECS")
+                    .ok()
+                    .and_then(|val| val.parse().ok())
+                    .unwrap_or(DEFAULT_CACHE_TTL_SECS),
+            ),
+        },
+    }
+}
+
+/// Build the closed-issue/closed-PR notice, preferring the user-supplied `reason` over `default_message`.
+fn closed_notice(reason: Option<String>, default_message: &str) -> BlockedOutcome {
+    BlockedOutcome::Notice {
+        level: hard_fail_level(),
+        message: reason.unwrap_or_else(|| default_message.to_string()),
+    }
+}
+
+/// Build a notice for anything that stopped us from getting a definitive issue/PR status: a request failure, a
+/// response we couldn't deserialize, or a state string we don't recognise. Uses [`hard_fail_level`] like
+/// [`closed_notice`] so `BLOCKED_HARD_FAIL` CI jobs don't silently treat "couldn't check" the same as "still open".
+fn unexpected_notice(message: String) -> BlockedOutcome {
+    BlockedOutcome::Notice {
+        level: hard_fail_level(),
+        message,
+    }
 }
 
 /// Try to parse a reference to an issue (in a few forms) and optionally a 'reason' from the input TokenStream.
@@ -134,115 +278,34 @@ fn parse_args(input: TokenStream) -> Result<(String, Option<String>), syn::Error
     ))
 }
 
-/// Get a client suitable for interacting with the Github API
-fn github_client(api_key: Option<&str>) -> reqwest::blocking::Client {
-    let mut headers = HeaderMap::new();
-    if let Some(api_key) = api_key {
-        headers.insert(
-            header::AUTHORIZATION,
-            header::HeaderValue::from_str(api_key).unwrap(),
-        );
-    }
-    headers.insert(
-        header::USER_AGENT,
-        header::HeaderValue::from_static("blocked-rs"),
-    );
-    reqwest::blocking::Client::builder()
-        .default_headers(headers)
-        .build()
-        .unwrap()
+/// Arguments to the `#[blocked_attr(...)]` attribute: an issue pattern and an optional `reason = "..."`.
+struct BlockedAttrArgs {
+    issue_pattern: String,
+    reason: Option<String>,
 }
 
-/// Parse an issue pattern. Possible forms are documented on the main `blocked!` macro
-fn parse_issue_pattern(pattern: &str) -> Result<Url, syn::Error> {
-    if URL.is_match(pattern) {
-        return Url::parse(pattern)
-            .map_err(|_| error("URL matched regex but was not accepted by the URL crate"));
-    }
-    if let Some(captures) = OWNERREPOISSUE.captures(pattern) {
-        return BASE
-            .clone()
-            .join(&format!(
-                "{}/{}/issues/{}",
-                captures.get(1).unwrap().as_str(),
-                captures.get(2).unwrap().as_str(),
-                captures.get(3).unwrap().as_str()
-            ))
-            .map_err(|_| error("Could not join URL fragments"));
-    }
-    if let Some(captures) = REPOISSUE.captures(pattern) {
-        let (org, _) = try_get_org_repo()?;
-        return BASE
-            .clone()
-            .join(&format!(
-                "{}/{}/issues/{}",
-                org,
-                captures.get(1).unwrap().as_str(),
-                captures.get(2).unwrap().as_str()
-            ))
-            .map_err(|_| error("Could not join URL fragments"));
-    }
-    if let Some(captures) = ISSUE.captures(pattern) {
-        let (org, repo) = try_get_org_repo()?;
-        return BASE
-            .clone()
-            .join(&format!(
-                "{}/{}/issues/{}",
-                org,
-                repo,
-                captures.get(1).unwrap().as_str()
-            ))
-            .map_err(|_| error("Could not join URL fragments"));
-    }
-    Err(error("Could not parse issue pattern"))
-}
-
-/// Try to get the organisation and repository from the current git repo.
-///
-/// This is used for shorthand issue patterns.
-fn try_get_org_repo() -> Result<(String, String), syn::Error> {
-    let repo = Repository::open_from_env()
-        .map_err(|_| error("Could not find or open a git repository"))?;
-
-    let remote = if let Ok(remote) = repo.find_remote("upstream") {
-        Some(remote)
-    } else {
-        repo.find_remote("origin").ok()
-    }
-    .ok_or_else(|| error("Could not find an 'upstream' or 'origin' remote"))?;
-
-    REMOTE
-        .captures(
-            remote
-                .url()
-                .ok_or_else(|| error("Remote URL not valid unicode"))?,
-        )
-        .map(|captures| {
-            (
-                captures
-                    .get(1)
-                    .unwrap_or_else(|| captures.get(3).unwrap())
-                    .as_str()
-                    .to_owned(),
-                captures
-                    .get(2)
-                    .unwrap_or_else(|| captures.get(4).unwrap())
-                    .as_str()
-                    .to_owned(),
-            )
+impl Parse for BlockedAttrArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let issue_pattern: LitStr = input.parse()?;
+        let reason = if input.is_empty() {
+            None
+        } else {
+            input.parse::<Token![,]>()?;
+            let key: syn::Ident = input.parse()?;
+            if key != "reason" {
+                return Err(syn::Error::new(key.span(), "Expected `reason`"));
+            }
+            input.parse::<Token![=]>()?;
+            let value: LitStr = input.parse()?;
+            Some(value.value())
+        };
+        Ok(BlockedAttrArgs {
+            issue_pattern: issue_pattern.value(),
+            reason,
         })
-        .ok_or_else(|| error("Failed to parse remote URL"))
+    }
 }
 
 fn error(message: impl AsRef<str>) -> syn::Error {
     syn::Error::new(proc_macro2::Span::call_site(), message.as_ref())
 }
-
-fn warning(message: impl AsRef<str>) {
-    Diagnostic::spanned(
-        [Span::call_site()].as_ref(),
-        Level::Warning,
-        message.as_ref(),
-    )
-    .emit()
-}